@@ -45,284 +45,610 @@ pub enum TokenType {
     True,
     Var,
     While,
-    Error(String),
     Eof,
 }
 
+/// A scanned lexeme together with the position it started at, so later
+/// compiler phases can report "error at line N, column C" diagnostics.
+#[derive(Debug, PartialEq)]
+pub struct Token {
+    pub kind: TokenType,
+    pub line: usize,
+    pub col: usize,
+    pub len: usize,
+    /// The exact source text this token was scanned from, e.g. `"1_000"` or
+    /// `"\"a\\nb\""` (unescaped) for a string literal. Kept separately from
+    /// `kind`'s decoded value so tools like `dump_tokens` can echo back what
+    /// the user actually wrote.
+    pub lexeme: String,
+}
+
+/// A lexical error found while scanning, with enough position information to
+/// point a user at the offending text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScanError {
+    pub message: String,
+    pub line: usize,
+    pub col: usize,
+}
+
 #[derive(Debug)]
 pub struct Scanner<'a> {
     source: Peekable<Chars<'a>>,
-    tokens: Vec<TokenType>,
     line: usize,
+    col: usize,
+    // Set once `Eof` has been yielded, so `next` can stop for good instead of
+    // re-scanning past the end of the source.
+    done: bool,
 }
 
-fn parse_number(mut scanner: Scanner<'_>) -> Scanner {
-    if scanner.source.peek().is_none() || !scanner.source.peek().unwrap().is_numeric() {
-        return scanner;
+impl<'a> Scanner<'a> {
+    /// Consumes and returns the next character, keeping `line`/`col` in sync.
+    fn advance(&mut self) -> Option<char> {
+        let ch = self.source.next();
+        if let Some(ch) = ch {
+            if ch == '\n' {
+                self.line += 1;
+                self.col = 1;
+            } else {
+                self.col += 1;
+            }
+        }
+        ch
     }
 
-    let mut number_string = String::new();
-
-    while let Some(&ch) = scanner.source.peek() {
-        if ch.is_numeric() || ch == '.' {
-            number_string.push(ch);
-            scanner.source.next();
+    fn skip_whitespace(&mut self) {
+        while let Some(&ch) = self.source.peek() {
+            match ch {
+                ' ' | '\r' | '\t' | '\n' => {
+                    self.advance();
+                }
+                _ => break,
+            }
+        }
+    }
 
+    /// Consumes a `//` line comment if one starts here, returning whether it did.
+    /// Uses a cloned lookahead so a lone `/` is left untouched for `parse_pontuation`.
+    fn skip_comment(&mut self) -> bool {
+        let mut probe = self.source.clone();
+        if probe.next() != Some('/') || probe.peek() != Some(&'/') {
+            return false;
+        }
+        while let Some(&ch) = self.source.peek() {
             if ch == '\n' {
-                scanner.line += 1;
-                scanner.source.next();
+                self.advance();
+                break;
             }
+            self.advance();
+        }
+        true
+    }
 
-            if ch == '.' {
-                if let Some(&next_ch) = scanner.source.peek() {
-                    if !next_ch.is_numeric() {
-                        scanner
-                            .tokens
-                            .push(TokenType::Error("Expected digit after '.'".to_string()));
-                        return scanner;
-                    }
-                }
+    fn skip_trivia(&mut self) {
+        loop {
+            self.skip_whitespace();
+            if !self.skip_comment() {
+                break;
             }
-        } else {
-            break;
         }
     }
 
-    if let Ok(number) = number_string.parse::<f64>() {
-        scanner.tokens.push(TokenType::Number(number));
-    } else {
-        scanner
-            .tokens
-            .push(TokenType::Error("Failed to parse number".to_string()));
+    /// Error recovery: discard the rest of the offending token by skipping
+    /// ahead to the next whitespace, so scanning can resume cleanly and
+    /// collect every lexical error in the source rather than stopping at
+    /// the first one.
+    fn synchronize(&mut self) {
+        while let Some(&ch) = self.source.peek() {
+            if ch.is_whitespace() {
+                break;
+            }
+            self.advance();
+        }
+    }
+
+    /// Looks one character past `peek()`. `Peekable<Chars>` only buffers a
+    /// single lookahead char, so this clones the (cheap, pointer-sized)
+    /// underlying iterator to read one further without consuming anything.
+    fn peek_next(&self) -> Option<char> {
+        let mut probe = self.source.clone();
+        probe.next();
+        probe.next()
     }
+}
+
+impl<'a> Iterator for Scanner<'a> {
+    type Item = Result<Token, ScanError>;
+
+    fn next(&mut self) -> Option<Result<Token, ScanError>> {
+        if self.done {
+            return None;
+        }
+
+        self.skip_trivia();
 
-    scanner
+        let Some(&ch) = self.source.peek() else {
+            self.done = true;
+            return Some(Ok(Token {
+                kind: TokenType::Eof,
+                line: self.line,
+                col: self.col,
+                len: 0,
+                lexeme: String::new(),
+            }));
+        };
+
+        let result = if ch.is_alphabetic() || ch == '_' {
+            Ok(parse_identifier(self))
+        } else if ch.is_numeric() {
+            parse_number(self)
+        } else if ch == '"' {
+            parse_string(self)
+        } else {
+            parse_pontuation(self)
+        };
+
+        if result.is_err() {
+            self.synchronize();
+        }
+        Some(result)
+    }
 }
 
-fn parse_string(mut scanner: Scanner<'_>) -> Scanner {
-    if scanner.source.peek().is_none() || scanner.source.peek() != Some(&'"') {
-        return scanner;
+fn parse_number(scanner: &mut Scanner<'_>) -> Result<Token, ScanError> {
+    let start_line = scanner.line;
+    let start_col = scanner.col;
+
+    if scanner.source.peek() == Some(&'0') {
+        match scanner.peek_next() {
+            Some('x') | Some('X') => return parse_radix_number(scanner, start_line, start_col, 16, "hexadecimal"),
+            Some('b') | Some('B') => return parse_radix_number(scanner, start_line, start_col, 2, "binary"),
+            _ => {}
+        }
     }
-    let mut string = String::new();
-    scanner.source.next(); // Consume opening '"'
+
+    let mut digits = String::new();
+    let mut lexeme = String::new();
+    let mut seen_dot = false;
+
     while let Some(&ch) = scanner.source.peek() {
-        if ch == '"' {
-            scanner.source.next();
+        if ch.is_numeric() {
+            digits.push(ch);
+            lexeme.push(ch);
+            scanner.advance();
+        } else if ch == '_' {
+            // Digit separator: counts toward the lexeme but not the value.
+            lexeme.push(ch);
+            scanner.advance();
+        } else if ch == '.' && !seen_dot && scanner.peek_next().is_some_and(|next| next.is_numeric()) {
+            // Only consume the '.' when it is actually a decimal point, so a
+            // trailing dot (`1.toString`, `1.2.3`) is left for `Dot` instead
+            // of being swallowed into the number.
+            seen_dot = true;
+            digits.push(ch);
+            lexeme.push(ch);
+            scanner.advance();
+        } else {
             break;
         }
-        string.push(ch);
-        scanner.source.next();
     }
-    scanner.tokens.push(TokenType::String(string));
-    scanner
+
+    let len = lexeme.chars().count();
+    match digits.parse::<f64>() {
+        Ok(number) => Ok(Token {
+            kind: TokenType::Number(number),
+            line: start_line,
+            col: start_col,
+            len,
+            lexeme,
+        }),
+        Err(_) => Err(ScanError {
+            message: "Failed to parse number".to_string(),
+            line: start_line,
+            col: start_col,
+        }),
+    }
 }
 
-fn parse_whitespace(mut scanner: Scanner<'_>) -> Scanner {
+/// Parses a `0x`/`0b` prefixed integer literal via `from_str_radix`.
+fn parse_radix_number(
+    scanner: &mut Scanner<'_>,
+    start_line: usize,
+    start_col: usize,
+    radix: u32,
+    name: &str,
+) -> Result<Token, ScanError> {
+    let mut lexeme = String::new();
+    lexeme.push(scanner.advance().expect("caller confirmed a leading '0'"));
+    lexeme.push(scanner.advance().expect("caller confirmed an 'x'/'b' prefix"));
+    let mut digits = String::new();
+
     while let Some(&ch) = scanner.source.peek() {
-        match ch {
-            ' ' | '\r' | '\t' => {
-                scanner.source.next();
-            }
-            '\n' => {
-                scanner.line += 1;
-                scanner.source.next();
-            }
-            _ => break,
+        if ch == '_' {
+            lexeme.push(ch);
+            scanner.advance();
+        } else if ch.is_digit(radix) {
+            digits.push(ch);
+            lexeme.push(ch);
+            scanner.advance();
+        } else {
+            break;
         }
     }
-    scanner
-}
 
-fn parse_comment(mut scanner: Scanner<'_>) -> Scanner {
-    if scanner.source.peek() == Some(&'/') {
-        scanner.source.next();
-        if scanner.source.peek() == Some(&'/') {
-            while let Some(&ch) = scanner.source.peek() {
-                if ch == '\n' {
-                    scanner.line += 1;
-                    scanner.source.next();
-                    break;
-                }
-                scanner.source.next();
-            }
-        }
+    if digits.is_empty() {
+        return Err(ScanError {
+            message: format!("Expected digits after {name} prefix"),
+            line: start_line,
+            col: start_col,
+        });
+    }
+
+    let len = lexeme.chars().count();
+    match i64::from_str_radix(&digits, radix) {
+        Ok(number) => Ok(Token {
+            kind: TokenType::Number(number as f64),
+            line: start_line,
+            col: start_col,
+            len,
+            lexeme,
+        }),
+        Err(_) => Err(ScanError {
+            message: format!("Malformed {name} literal"),
+            line: start_line,
+            col: start_col,
+        }),
     }
-    scanner
 }
 
-fn parse_identifier(mut scanner: Scanner<'_>) -> Scanner {
-    match scanner.source.peek() {
-        Some(&ch) if ch.is_alphabetic() || ch == '_' => {
-            let mut identifier = String::new();
-            while let Some(&ch) = scanner.source.peek() {
-                if ch.is_alphanumeric() || ch == '_' {
-                    identifier.push(ch);
-                    scanner.source.next();
-                } else {
-                    break;
+/// Consumes the rest of a string literal that has already been found
+/// malformed (e.g. an unknown escape), stopping right after its closing `"`
+/// (or at EOF if it has none). String bodies routinely
+/// contain whitespace, so the generic whitespace-based `Scanner::synchronize`
+/// would otherwise stop mid-string, leaving the real closing quote to be
+/// rescanned as the start of a bogus second string.
+fn recover_unterminated_string(scanner: &mut Scanner<'_>) {
+    while let Some(&ch) = scanner.source.peek() {
+        match ch {
+            '"' => {
+                scanner.advance();
+                break;
+            }
+            '\\' => {
+                // Skip the escaped character too, so an escaped `\"` isn't
+                // mistaken for the closing quote.
+                scanner.advance();
+                if scanner.source.peek().is_some() {
+                    scanner.advance();
                 }
             }
-
-            // check against the reserved keywords
-            match identifier.as_str() {
-                "and" => scanner.tokens.push(TokenType::And),
-                "class" => scanner.tokens.push(TokenType::Class),
-                "else" => scanner.tokens.push(TokenType::Else),
-                "false" => scanner.tokens.push(TokenType::False),
-                "fun" => scanner.tokens.push(TokenType::Fun),
-                "for" => scanner.tokens.push(TokenType::For),
-                "if" => scanner.tokens.push(TokenType::If),
-                "nil" => scanner.tokens.push(TokenType::Nil),
-                "or" => scanner.tokens.push(TokenType::Or),
-                "print" => scanner.tokens.push(TokenType::Print),
-                "return" => scanner.tokens.push(TokenType::Return),
-                "super" => scanner.tokens.push(TokenType::Super),
-                "this" => scanner.tokens.push(TokenType::This),
-                "true" => scanner.tokens.push(TokenType::True),
-                "var" => scanner.tokens.push(TokenType::Var),
-                "while" => scanner.tokens.push(TokenType::While),
-                _ => scanner.tokens.push(TokenType::Identifier(identifier)),
+            _ => {
+                scanner.advance();
             }
-            scanner
         }
-        _ => scanner,
     }
 }
 
-fn parse_pontuation(mut scanner: Scanner<'_>) -> Scanner {
-    match scanner.source.peek() {
-        Some(&ch) => match ch {
-            '(' => {
-                scanner.tokens.push(TokenType::LeftParen);
-                scanner.source.next();
-                scanner
-            }
-            ')' => {
-                scanner.tokens.push(TokenType::RightParen);
-                scanner.source.next();
-                scanner
-            }
-            '{' => {
-                scanner.tokens.push(TokenType::LeftBrace);
-                scanner.source.next();
-                scanner
-            }
-            '}' => {
-                scanner.tokens.push(TokenType::RightBrace);
-                scanner.source.next();
-                scanner
-            }
-            ',' => {
-                scanner.tokens.push(TokenType::Comma);
-                scanner.source.next();
-                scanner
-            }
-            '.' => {
-                scanner.tokens.push(TokenType::Dot);
-                scanner.source.next();
-                scanner
-            }
-            '-' => {
-                scanner.tokens.push(TokenType::Minus);
-                scanner.source.next();
-                scanner
-            }
-            '+' => {
-                scanner.tokens.push(TokenType::Plus);
-                scanner.source.next();
-                scanner
-            }
-            '/' => {
-                scanner.tokens.push(TokenType::Slash);
-                scanner.source.next();
-                scanner
-            }
-            ';' => {
-                scanner.tokens.push(TokenType::Semicolon);
-                scanner.source.next();
-                scanner
-            }
-            '*' => {
-                scanner.tokens.push(TokenType::Star);
-                scanner.source.next();
-                scanner
+fn parse_string(scanner: &mut Scanner<'_>) -> Result<Token, ScanError> {
+    let start_line = scanner.line;
+    let start_col = scanner.col;
+    let mut string = String::new();
+    let mut lexeme = String::from("\"");
+    scanner.advance(); // Consume opening '"'
+
+    loop {
+        match scanner.source.peek() {
+            None => {
+                return Err(ScanError {
+                    message: "Unterminated string".to_string(),
+                    line: start_line,
+                    col: start_col,
+                });
             }
-            '!' => {
-                scanner.source.next();
-                match scanner.source.peek() {
-                    Some(&'=') => {
-                        scanner.tokens.push(TokenType::BangEqual);
-                        scanner.source.next();
-                    }
-                    _ => scanner.tokens.push(TokenType::Bang),
-                }
-                scanner
+            Some(&'"') => {
+                scanner.advance();
+                lexeme.push('"');
+                break;
             }
-            '=' => {
-                scanner.source.next();
+            Some(&'\\') => {
+                scanner.advance();
+                lexeme.push('\\');
                 match scanner.source.peek() {
-                    Some(&'=') => {
-                        scanner.tokens.push(TokenType::EqualEqual);
-                        scanner.source.next();
+                    Some(&escape) => {
+                        let decoded = match escape {
+                            'n' => '\n',
+                            't' => '\t',
+                            'r' => '\r',
+                            '\\' => '\\',
+                            '"' => '"',
+                            '0' => '\0',
+                            _ => {
+                                let message = format!("Unknown escape sequence '\\{escape}'");
+                                scanner.advance(); // the invalid escape character itself
+                                recover_unterminated_string(scanner);
+                                return Err(ScanError {
+                                    message,
+                                    line: start_line,
+                                    col: start_col,
+                                });
+                            }
+                        };
+                        scanner.advance();
+                        lexeme.push(escape);
+                        string.push(decoded);
                     }
-                    _ => scanner.tokens.push(TokenType::Equal),
-                }
-                scanner
-            }
-            '<' => {
-                scanner.source.next();
-                match scanner.source.peek() {
-                    Some(&'=') => {
-                        scanner.tokens.push(TokenType::LessEqual);
-                        scanner.source.next();
+                    None => {
+                        return Err(ScanError {
+                            message: "Unterminated string".to_string(),
+                            line: start_line,
+                            col: start_col,
+                        });
                     }
-                    _ => scanner.tokens.push(TokenType::Less),
                 }
-                scanner
             }
-            '>' => {
-                scanner.source.next();
-                match scanner.source.peek() {
-                    Some(&'=') => {
-                        scanner.tokens.push(TokenType::GreaterEqual);
-                        scanner.source.next();
-                    }
-                    _ => scanner.tokens.push(TokenType::Greater),
-                }
-                scanner
+            Some(&ch) => {
+                // `advance` itself bumps `scanner.line` when `ch` is a literal
+                // newline, so multi-line strings keep accurate positions.
+                string.push(ch);
+                lexeme.push(ch);
+                scanner.advance();
             }
-            _ => scanner,
-        },
-        _ => scanner,
+        }
+    }
+
+    let len = lexeme.chars().count();
+    Ok(Token {
+        kind: TokenType::String(string),
+        line: start_line,
+        col: start_col,
+        len,
+        lexeme,
+    })
+}
+
+fn parse_identifier(scanner: &mut Scanner<'_>) -> Token {
+    let start_line = scanner.line;
+    let start_col = scanner.col;
+    let mut identifier = String::new();
+    while let Some(&ch) = scanner.source.peek() {
+        if ch.is_alphanumeric() || ch == '_' {
+            identifier.push(ch);
+            scanner.advance();
+        } else {
+            break;
+        }
+    }
+
+    let len = identifier.chars().count();
+    let kind = match identifier.as_str() {
+        "and" => TokenType::And,
+        "class" => TokenType::Class,
+        "else" => TokenType::Else,
+        "false" => TokenType::False,
+        "fun" => TokenType::Fun,
+        "for" => TokenType::For,
+        "if" => TokenType::If,
+        "nil" => TokenType::Nil,
+        "or" => TokenType::Or,
+        "print" => TokenType::Print,
+        "return" => TokenType::Return,
+        "super" => TokenType::Super,
+        "this" => TokenType::This,
+        "true" => TokenType::True,
+        "var" => TokenType::Var,
+        "while" => TokenType::While,
+        _ => TokenType::Identifier(identifier.clone()),
+    };
+    Token {
+        kind,
+        line: start_line,
+        col: start_col,
+        len,
+        lexeme: identifier,
     }
 }
 
-fn pipe(scanner: Scanner<'_>, functions: Vec<fn(Scanner<'_>) -> Scanner<'_>>) -> Scanner<'_> {
-    functions.into_iter().fold(scanner, |acc, f| f(acc))
+fn parse_pontuation(scanner: &mut Scanner<'_>) -> Result<Token, ScanError> {
+    let line = scanner.line;
+    let col = scanner.col;
+    let ch = *scanner
+        .source
+        .peek()
+        .expect("next() only dispatches here when a char is available");
+
+    macro_rules! token {
+        ($kind:expr, $lexeme:expr) => {
+            Token { kind: $kind, line, col, len: $lexeme.chars().count(), lexeme: $lexeme.to_string() }
+        };
+    }
+
+    let token = match ch {
+        '(' => {
+            scanner.advance();
+            token!(TokenType::LeftParen, "(")
+        }
+        ')' => {
+            scanner.advance();
+            token!(TokenType::RightParen, ")")
+        }
+        '{' => {
+            scanner.advance();
+            token!(TokenType::LeftBrace, "{")
+        }
+        '}' => {
+            scanner.advance();
+            token!(TokenType::RightBrace, "}")
+        }
+        ',' => {
+            scanner.advance();
+            token!(TokenType::Comma, ",")
+        }
+        '.' => {
+            scanner.advance();
+            token!(TokenType::Dot, ".")
+        }
+        '-' => {
+            scanner.advance();
+            token!(TokenType::Minus, "-")
+        }
+        '+' => {
+            scanner.advance();
+            token!(TokenType::Plus, "+")
+        }
+        '/' => {
+            scanner.advance();
+            token!(TokenType::Slash, "/")
+        }
+        ';' => {
+            scanner.advance();
+            token!(TokenType::Semicolon, ";")
+        }
+        '*' => {
+            scanner.advance();
+            token!(TokenType::Star, "*")
+        }
+        '!' => {
+            scanner.advance();
+            if scanner.source.peek() == Some(&'=') {
+                scanner.advance();
+                token!(TokenType::BangEqual, "!=")
+            } else {
+                token!(TokenType::Bang, "!")
+            }
+        }
+        '=' => {
+            scanner.advance();
+            if scanner.source.peek() == Some(&'=') {
+                scanner.advance();
+                token!(TokenType::EqualEqual, "==")
+            } else {
+                token!(TokenType::Equal, "=")
+            }
+        }
+        '<' => {
+            scanner.advance();
+            if scanner.source.peek() == Some(&'=') {
+                scanner.advance();
+                token!(TokenType::LessEqual, "<=")
+            } else {
+                token!(TokenType::Less, "<")
+            }
+        }
+        '>' => {
+            scanner.advance();
+            if scanner.source.peek() == Some(&'=') {
+                scanner.advance();
+                token!(TokenType::GreaterEqual, ">=")
+            } else {
+                token!(TokenType::Greater, ">")
+            }
+        }
+        _ => {
+            scanner.advance();
+            return Err(ScanError {
+                message: format!("Unexpected character '{ch}'"),
+                line,
+                col,
+            });
+        }
+    };
+
+    Ok(token)
 }
 
-pub fn scan(source: &str) -> Scanner {
-    let functions = vec![
-        parse_whitespace,
-        parse_comment,
-        parse_identifier,
-        parse_number,
-        parse_string,
-        parse_pontuation,
-    ];
-    let mut scanner = Scanner {
+/// Builds a lazy token scanner over `source`. Tokens are produced one at a
+/// time as the returned `Scanner` is iterated (`for token in &mut scanner`),
+/// so nothing downstream has to wait for the whole source to be tokenized.
+/// Malformed lexemes surface as `Err(ScanError)` without stopping the scan,
+/// so a single pass collects every lexical error in the source.
+pub fn scan(source: &str) -> Scanner<'_> {
+    Scanner {
         source: source.chars().peekable(),
-        tokens: Vec::new(),
         line: 1,
-    };
-    while scanner.source.peek().is_some() {
-        match scanner.tokens.last() {
-            Some(TokenType::Error(_)) => return scanner,
-            _ => scanner = pipe(scanner, functions.clone()),
+        col: 1,
+        done: false,
+    }
+}
+
+/// Short variant name for a `TokenType`, independent of its decoded payload
+/// (e.g. `Number(123.45)` -> `"Number"`). Used by `dump_tokens` so the dump
+/// shows the token kind and its original lexeme side by side instead of
+/// Rust's `Debug` output for the decoded value.
+fn token_type_name(kind: &TokenType) -> &'static str {
+    match kind {
+        TokenType::LeftParen => "LeftParen",
+        TokenType::RightParen => "RightParen",
+        TokenType::LeftBrace => "LeftBrace",
+        TokenType::RightBrace => "RightBrace",
+        TokenType::Comma => "Comma",
+        TokenType::Dot => "Dot",
+        TokenType::Minus => "Minus",
+        TokenType::Plus => "Plus",
+        TokenType::Semicolon => "Semicolon",
+        TokenType::Slash => "Slash",
+        TokenType::Star => "Star",
+        TokenType::Bang => "Bang",
+        TokenType::BangEqual => "BangEqual",
+        TokenType::Equal => "Equal",
+        TokenType::EqualEqual => "EqualEqual",
+        TokenType::Greater => "Greater",
+        TokenType::GreaterEqual => "GreaterEqual",
+        TokenType::Less => "Less",
+        TokenType::LessEqual => "LessEqual",
+        TokenType::Identifier(_) => "Identifier",
+        TokenType::String(_) => "String",
+        TokenType::Number(_) => "Number",
+        TokenType::And => "And",
+        TokenType::Class => "Class",
+        TokenType::Else => "Else",
+        TokenType::False => "False",
+        TokenType::Fun => "Fun",
+        TokenType::For => "For",
+        TokenType::If => "If",
+        TokenType::Nil => "Nil",
+        TokenType::Or => "Or",
+        TokenType::Print => "Print",
+        TokenType::Return => "Return",
+        TokenType::Super => "Super",
+        TokenType::This => "This",
+        TokenType::True => "True",
+        TokenType::Var => "Var",
+        TokenType::While => "While",
+        TokenType::Eof => "Eof",
+    }
+}
+
+/// A `--tokens`-style debugging aid: scans `source` and prints one line per
+/// token as `<line> <type> '<lexeme>'`, collapsing a run of tokens on the
+/// same line into a `|` continuation marker, mirroring the token-dump mode
+/// of the crafting-interpreters front-end. Lexical errors are printed inline
+/// and scanning continues past them.
+pub fn dump_tokens(source: &str) {
+    let mut last_line: Option<usize> = None;
+
+    for result in scan(source) {
+        let line = match &result {
+            Ok(token) => token.line,
+            Err(err) => err.line,
+        };
+        if last_line == Some(line) {
+            print!("   | ");
+        } else {
+            print!("{line:4} ");
+            last_line = Some(line);
+        }
+
+        match result {
+            Ok(token) => {
+                let is_eof = token.kind == TokenType::Eof;
+                println!("{} '{}'", token_type_name(&token.kind), token.lexeme);
+                if is_eof {
+                    break;
+                }
+            }
+            Err(err) => println!("Error: {} (col {})", err.message, err.col),
         }
     }
-    scanner.tokens.push(TokenType::Eof);
-    scanner
 }
 
 #[cfg(test)]
@@ -330,56 +656,256 @@ mod tests {
     use super::*;
 
     macro_rules! assert_token {
-        ($scanner:expr, $index:expr, $token:expr) => {
-            assert_eq!($scanner.tokens[$index], $token);
+        ($tokens:expr, $index:expr, $token:expr) => {
+            assert_eq!($tokens[$index].kind, $token);
         };
     }
+
+    fn scan_ok(source: &str) -> Vec<Token> {
+        scan(source).collect::<Result<Vec<Token>, ScanError>>().unwrap()
+    }
+
     #[test]
     fn test_scan() {
         let source = "( ) { } , . - + ; * ! != = == > >= < <= identifier \"string\" 123.45 and class else false fun for if nil or print return super this true var while\n // comment\n 1/3";
-        let scanner = scan(source);
-        println!("{:?}", scanner.tokens);
-        assert_eq!(scanner.tokens.len(), 41);
-        assert_token!(scanner, 0, TokenType::LeftParen);
-        assert_token!(scanner, 1, TokenType::RightParen);
-        assert_token!(scanner, 2, TokenType::LeftBrace);
-        assert_token!(scanner, 3, TokenType::RightBrace);
-        assert_token!(scanner, 4, TokenType::Comma);
-        assert_token!(scanner, 5, TokenType::Dot);
-        assert_token!(scanner, 6, TokenType::Minus);
-        assert_token!(scanner, 7, TokenType::Plus);
-        assert_token!(scanner, 8, TokenType::Semicolon);
-        assert_token!(scanner, 9, TokenType::Star);
-        assert_token!(scanner, 10, TokenType::Bang);
-        assert_token!(scanner, 11, TokenType::BangEqual);
-        assert_token!(scanner, 12, TokenType::Equal);
-        assert_token!(scanner, 13, TokenType::EqualEqual);
-        assert_token!(scanner, 14, TokenType::Greater);
-        assert_token!(scanner, 15, TokenType::GreaterEqual);
-        assert_token!(scanner, 16, TokenType::Less);
-        assert_token!(scanner, 17, TokenType::LessEqual);
-        assert_token!(scanner, 18, TokenType::Identifier("identifier".to_string()));
-        assert_token!(scanner, 19, TokenType::String("string".to_string()));
-        assert_token!(scanner, 20, TokenType::Number(123.45));
-        assert_token!(scanner, 21, TokenType::And);
-        assert_token!(scanner, 22, TokenType::Class);
-        assert_token!(scanner, 23, TokenType::Else);
-        assert_token!(scanner, 24, TokenType::False);
-        assert_token!(scanner, 25, TokenType::Fun);
-        assert_token!(scanner, 26, TokenType::For);
-        assert_token!(scanner, 27, TokenType::If);
-        assert_token!(scanner, 28, TokenType::Nil);
-        assert_token!(scanner, 29, TokenType::Or);
-        assert_token!(scanner, 30, TokenType::Print);
-        assert_token!(scanner, 31, TokenType::Return);
-        assert_token!(scanner, 32, TokenType::Super);
-        assert_token!(scanner, 33, TokenType::This);
-        assert_token!(scanner, 34, TokenType::True);
-        assert_token!(scanner, 35, TokenType::Var);
-        assert_token!(scanner, 36, TokenType::While);
-        assert_token!(scanner, 37, TokenType::Number(1.0));
-        assert_token!(scanner, 38, TokenType::Slash);
-        assert_token!(scanner, 39, TokenType::Number(3.0));
-        assert_token!(scanner, 40, TokenType::Eof);
+        let tokens = scan_ok(source);
+        println!("{:?}", tokens);
+        assert_eq!(tokens.len(), 41);
+        assert_token!(tokens, 0, TokenType::LeftParen);
+        assert_token!(tokens, 1, TokenType::RightParen);
+        assert_token!(tokens, 2, TokenType::LeftBrace);
+        assert_token!(tokens, 3, TokenType::RightBrace);
+        assert_token!(tokens, 4, TokenType::Comma);
+        assert_token!(tokens, 5, TokenType::Dot);
+        assert_token!(tokens, 6, TokenType::Minus);
+        assert_token!(tokens, 7, TokenType::Plus);
+        assert_token!(tokens, 8, TokenType::Semicolon);
+        assert_token!(tokens, 9, TokenType::Star);
+        assert_token!(tokens, 10, TokenType::Bang);
+        assert_token!(tokens, 11, TokenType::BangEqual);
+        assert_token!(tokens, 12, TokenType::Equal);
+        assert_token!(tokens, 13, TokenType::EqualEqual);
+        assert_token!(tokens, 14, TokenType::Greater);
+        assert_token!(tokens, 15, TokenType::GreaterEqual);
+        assert_token!(tokens, 16, TokenType::Less);
+        assert_token!(tokens, 17, TokenType::LessEqual);
+        assert_token!(tokens, 18, TokenType::Identifier("identifier".to_string()));
+        assert_token!(tokens, 19, TokenType::String("string".to_string()));
+        assert_token!(tokens, 20, TokenType::Number(123.45));
+        assert_token!(tokens, 21, TokenType::And);
+        assert_token!(tokens, 22, TokenType::Class);
+        assert_token!(tokens, 23, TokenType::Else);
+        assert_token!(tokens, 24, TokenType::False);
+        assert_token!(tokens, 25, TokenType::Fun);
+        assert_token!(tokens, 26, TokenType::For);
+        assert_token!(tokens, 27, TokenType::If);
+        assert_token!(tokens, 28, TokenType::Nil);
+        assert_token!(tokens, 29, TokenType::Or);
+        assert_token!(tokens, 30, TokenType::Print);
+        assert_token!(tokens, 31, TokenType::Return);
+        assert_token!(tokens, 32, TokenType::Super);
+        assert_token!(tokens, 33, TokenType::This);
+        assert_token!(tokens, 34, TokenType::True);
+        assert_token!(tokens, 35, TokenType::Var);
+        assert_token!(tokens, 36, TokenType::While);
+        assert_token!(tokens, 37, TokenType::Number(1.0));
+        assert_token!(tokens, 38, TokenType::Slash);
+        assert_token!(tokens, 39, TokenType::Number(3.0));
+        assert_token!(tokens, 40, TokenType::Eof);
+    }
+
+    #[test]
+    fn test_token_positions() {
+        let tokens = scan_ok("var x = 1;\n  foo");
+        assert_eq!(tokens[0].line, 1);
+        assert_eq!(tokens[0].col, 1);
+        assert_eq!(tokens[0].len, 3);
+
+        assert_eq!(tokens[1].line, 1);
+        assert_eq!(tokens[1].col, 5);
+        assert_eq!(tokens[1].len, 1);
+
+        // `foo` starts on the second line, indented by two spaces.
+        let foo = &tokens[5];
+        assert_eq!(foo.kind, TokenType::Identifier("foo".to_string()));
+        assert_eq!(foo.line, 2);
+        assert_eq!(foo.col, 3);
+    }
+
+    #[test]
+    fn test_scan_is_lazy() {
+        // Only pulling one token should not force the rest of the source to
+        // be scanned; the second `var` should still be sitting untouched.
+        let mut scanner = scan("var x; var y;");
+        let first = scanner.next().unwrap().unwrap();
+        assert_eq!(first.kind, TokenType::Var);
+
+        let remaining = (&mut scanner)
+            .collect::<Result<Vec<Token>, ScanError>>()
+            .unwrap();
+        assert_eq!(remaining.len(), 6); // x ; var y ; Eof
+        assert_token!(remaining, 2, TokenType::Var);
+    }
+
+    #[test]
+    fn test_scan_collects_every_error_in_one_pass() {
+        // Two unrelated malformed tokens, separated by valid ones: both
+        // errors should surface, and the valid tokens around them should
+        // still scan correctly instead of the whole pass dying at the first.
+        let results: Vec<Result<Token, ScanError>> = scan("@ 1 # 2").collect();
+
+        let errors: Vec<&ScanError> = results.iter().filter_map(|r| r.as_ref().err()).collect();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].message, "Unexpected character '@'");
+        assert_eq!(errors[1].message, "Unexpected character '#'");
+
+        let tokens: Vec<&Token> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+        assert_eq!(tokens.len(), 3); // 1, 2, Eof
+        assert_eq!(tokens[0].kind, TokenType::Number(1.0));
+        assert_eq!(tokens[1].kind, TokenType::Number(2.0));
+        assert_eq!(tokens[2].kind, TokenType::Eof);
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let tokens = scan_ok(r#""a\nb\tc\r\\\"\0d""#);
+        assert_token!(
+            tokens,
+            0,
+            TokenType::String("a\nb\tc\r\\\"\0d".to_string())
+        );
+    }
+
+    #[test]
+    fn test_string_unknown_escape_is_an_error() {
+        let results: Vec<Result<Token, ScanError>> = scan(r#""bad \q escape""#).collect();
+        assert_eq!(results[0], Err(ScanError {
+            message: "Unknown escape sequence '\\q'".to_string(),
+            line: 1,
+            col: 1,
+        }));
+    }
+
+    #[test]
+    fn test_scan_resumes_after_the_malformed_string_closing_quote() {
+        // The rest of the malformed string (including its closing `"`) must
+        // be consumed as part of recovering from the error, not left for the
+        // generic whitespace-based synchronize to stumble into: otherwise the
+        // real closing quote gets rescanned as if it opened a second string.
+        let results: Vec<Result<Token, ScanError>> =
+            scan(r#""bad \q escape" identifier"#).collect();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0], Err(ScanError {
+            message: "Unknown escape sequence '\\q'".to_string(),
+            line: 1,
+            col: 1,
+        }));
+        assert_eq!(
+            results[1],
+            Ok(Token {
+                kind: TokenType::Identifier("identifier".to_string()),
+                line: 1,
+                col: 17,
+                len: 10,
+                lexeme: "identifier".to_string(),
+            })
+        );
+        assert_eq!(results[2].as_ref().unwrap().kind, TokenType::Eof);
+    }
+
+    #[test]
+    fn test_unterminated_string_reports_opening_position() {
+        let results: Vec<Result<Token, ScanError>> = scan("  \"never closed").collect();
+        assert_eq!(results[0], Err(ScanError {
+            message: "Unterminated string".to_string(),
+            line: 1,
+            col: 3,
+        }));
+    }
+
+    #[test]
+    fn test_multiline_string_tracks_line_number() {
+        let tokens = scan_ok("\"line one\nline two\"\nidentifier");
+        assert_token!(
+            tokens,
+            0,
+            TokenType::String("line one\nline two".to_string())
+        );
+        // One literal newline inside the string plus the newline after the
+        // closing quote puts `identifier` on line 3.
+        assert_eq!(tokens[1].line, 3);
+        assert_token!(tokens, 1, TokenType::Identifier("identifier".to_string()));
+    }
+
+    #[test]
+    fn test_trailing_dot_is_not_swallowed_by_the_number() {
+        // A `.` that isn't followed by a digit belongs to the next token,
+        // not the number: `1.2.3` is two numbers and a `Dot`, and `1.toString`
+        // is a number followed by member access.
+        let tokens = scan_ok("1.2.3");
+        assert_eq!(tokens.len(), 4);
+        assert_token!(tokens, 0, TokenType::Number(1.2));
+        assert_token!(tokens, 1, TokenType::Dot);
+        assert_token!(tokens, 2, TokenType::Number(3.0));
+
+        let tokens = scan_ok("1.toString");
+        assert_eq!(tokens.len(), 4);
+        assert_token!(tokens, 0, TokenType::Number(1.0));
+        assert_token!(tokens, 1, TokenType::Dot);
+        assert_token!(tokens, 2, TokenType::Identifier("toString".to_string()));
+    }
+
+    #[test]
+    fn test_number_digit_separators() {
+        let tokens = scan_ok("1_000_000.5");
+        assert_token!(tokens, 0, TokenType::Number(1_000_000.5));
+    }
+
+    #[test]
+    fn test_hex_and_binary_literals() {
+        let tokens = scan_ok("0x1A 0b101 0x1_F");
+        assert_token!(tokens, 0, TokenType::Number(26.0));
+        assert_token!(tokens, 1, TokenType::Number(5.0));
+        assert_token!(tokens, 2, TokenType::Number(31.0));
+    }
+
+    #[test]
+    fn test_hex_literal_with_no_digits_is_an_error() {
+        let results: Vec<Result<Token, ScanError>> = scan("0x").collect();
+        assert_eq!(
+            results[0],
+            Err(ScanError {
+                message: "Expected digits after hexadecimal prefix".to_string(),
+                line: 1,
+                col: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_token_lexeme_is_the_original_source_text() {
+        let tokens = scan_ok(r#"1_000 "a\nb" != identifier"#);
+        assert_eq!(tokens[0].lexeme, "1_000");
+        assert_eq!(tokens[1].lexeme, "\"a\\nb\"");
+        assert_eq!(tokens[2].lexeme, "!=");
+        assert_eq!(tokens[3].lexeme, "identifier");
+    }
+
+    #[test]
+    fn test_token_type_name_ignores_the_decoded_payload() {
+        assert_eq!(token_type_name(&TokenType::Number(123.45)), "Number");
+        assert_eq!(
+            token_type_name(&TokenType::Identifier("x".to_string())),
+            "Identifier"
+        );
+        assert_eq!(token_type_name(&TokenType::Plus), "Plus");
+    }
+
+    #[test]
+    fn test_dump_tokens_does_not_panic() {
+        // `dump_tokens` just prints, but it should complete the full scan
+        // (errors included) without panicking.
+        dump_tokens("var x = 1;\nprint x + @;");
     }
 }